@@ -0,0 +1,111 @@
+// Licensed under the Apache License, Version 2.0 or the MIT License.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright Tock Contributors 2024.
+
+//! Frozen snapshot of the derived clock-tree frequencies.
+//!
+//! Once [`Clocks`](super::Clocks) configuration is final the bus frequencies no
+//! longer change, so re-deriving them from `Rcc` on every
+//! [`get_frequency`](super::PeripheralClockInterface::get_frequency) call is
+//! wasted work on hot paths such as baud-rate and timer-period computation.
+//! [`freeze`] records them once into a module-level snapshot and [`get_freqs`]
+//! hands back an immutable copy, making the "configuration can no longer
+//! change" invariant explicit.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::rcc::Rcc;
+
+/// Immutable snapshot of the derived clock-tree frequencies, in Hz.
+#[derive(Copy, Clone)]
+pub struct ClockFrequencies {
+    /// AHB bus (HCLK) frequency.
+    pub ahb: u32,
+    /// APB1 bus (PCLK1) frequency.
+    pub apb1: u32,
+    /// APB2 bus (PCLK2) frequency.
+    pub apb2: u32,
+    /// Clock seen by APB1 timers, after the TIMPRE doubling rule.
+    pub apb1_timer: u32,
+    /// Clock seen by APB2 timers, after the TIMPRE doubling rule.
+    pub apb2_timer: u32,
+}
+
+impl ClockFrequencies {
+    /// Derive the snapshot from the live `Rcc` configuration.
+    pub fn compute(rcc: &Rcc) -> Self {
+        let ahb = rcc.get_sys_clock_frequency();
+        let apb1_prescaler = rcc.get_apb1_prescaler();
+        let apb2_prescaler = rcc.get_apb2_prescaler();
+        Self {
+            ahb: ahb as u32,
+            apb1: (ahb / usize::from(apb1_prescaler)) as u32,
+            apb2: (ahb / usize::from(apb2_prescaler)) as u32,
+            apb1_timer: super::periph::tim_freq(rcc, ahb, apb1_prescaler) as u32,
+            apb2_timer: super::periph::tim_freq(rcc, ahb, apb2_prescaler) as u32,
+        }
+    }
+}
+
+struct FrozenFrequencies {
+    frozen: AtomicBool,
+    freqs: UnsafeCell<ClockFrequencies>,
+}
+
+// SAFETY: the snapshot is written exactly once, during single-threaded clock
+// configuration, before `frozen` is published with release ordering; every
+// later access is a read guarded by an acquire load of `frozen`.
+unsafe impl Sync for FrozenFrequencies {}
+
+static FROZEN: FrozenFrequencies = FrozenFrequencies {
+    frozen: AtomicBool::new(false),
+    freqs: UnsafeCell::new(ClockFrequencies {
+        ahb: 0,
+        apb1: 0,
+        apb2: 0,
+        apb1_timer: 0,
+        apb2_timer: 0,
+    }),
+};
+
+/// Derive the snapshot from `rcc` and freeze it.
+///
+/// This is the hook the `Clocks` configuration path calls once clock setup is
+/// final (it owns the `Rcc` reference); it is equivalent to
+/// `freeze(ClockFrequencies::compute(rcc))`.
+pub fn freeze_from(rcc: &Rcc) {
+    freeze(ClockFrequencies::compute(rcc));
+}
+
+/// Record the final clock frequencies.
+///
+/// Must be called once, after clock configuration is complete. Panics if the
+/// frequencies have already been frozen.
+pub fn freeze(freqs: ClockFrequencies) {
+    assert!(
+        !FROZEN.frozen.load(Ordering::Acquire),
+        "clock frequencies already frozen"
+    );
+    // SAFETY: no reader can observe the snapshot until `frozen` is published
+    // below, and this is the only writer.
+    unsafe {
+        *FROZEN.freqs.get() = freqs;
+    }
+    FROZEN.frozen.store(true, Ordering::Release);
+}
+
+/// Return the frozen clock-frequency snapshot.
+///
+/// Panics if the frequencies have not been frozen yet; the `Clocks`
+/// configuration path publishes them via [`freeze_from`] before any peripheral
+/// queries its clock.
+pub fn get_freqs() -> ClockFrequencies {
+    assert!(
+        FROZEN.frozen.load(Ordering::Acquire),
+        "clock frequencies queried before being frozen"
+    );
+    // SAFETY: `frozen` is set, so the snapshot is fully initialized and is never
+    // mutated again.
+    unsafe { *FROZEN.freqs.get() }
+}
@@ -0,0 +1,72 @@
+// Licensed under the Apache License, Version 2.0 or the MIT License.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright Tock Contributors 2024.
+
+//! Clock-tree configuration for the STM32F4xx.
+
+pub mod freqs;
+pub mod periph;
+pub mod pwr;
+
+pub use freqs::{freeze, freeze_from, get_freqs, ClockFrequencies};
+pub use periph::{
+    GpioClocks, PeripheralClock, PeripheralClockInterface, PeripheralClockType, PeripheralClocks,
+    HCLK1, HCLK2, HCLK3, PCLK1, PCLK2,
+};
+pub use pwr::{Pwr, PwrRegisters, VoltageScale};
+
+use core::marker::PhantomData;
+
+use crate::chip_specific::ChipSpecs;
+use crate::rcc::Rcc;
+use kernel::ErrorCode;
+
+/// Entry point for configuring the chip's clock tree.
+pub struct Clocks<'a, C: ChipSpecs> {
+    rcc: &'a Rcc,
+    pwr: Pwr,
+    _marker: PhantomData<C>,
+}
+
+impl<'a, C: ChipSpecs> Clocks<'a, C> {
+    pub fn new(rcc: &'a Rcc) -> Self {
+        Self {
+            rcc,
+            pwr: Pwr::new_default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configure the system clock to `frequency` (Hz).
+    ///
+    /// Selects the minimum regulator voltage scale that legally supports the
+    /// requested frequency — rejecting anything beyond the highest supported
+    /// scale with [`ErrorCode::INVAL`] — and raises the scale (enabling
+    /// over-drive if needed) *before* the clock switch. After the switch it
+    /// drops over-drive when the chosen scale no longer needs it.
+    ///
+    /// Once the clock tree is programmed the bus frequencies can no longer
+    /// change, so this publishes a frozen snapshot of them that peripherals
+    /// query cheaply through [`get_freqs`], instead of re-deriving them from
+    /// `Rcc` on every call.
+    pub fn set_sys_clock_frequency(&self, frequency: u32) -> Result<(), ErrorCode> {
+        // Raise the regulator voltage before increasing the clock; this also
+        // rejects frequencies that no voltage scale supports.
+        let scale = self.pwr.configure_for_sys_clock(frequency)?;
+
+        // Program the PLL and switch the system clock source.
+        self.rcc.set_sys_clock_frequency(frequency);
+
+        // Over-drive is only legal at the top of the range; drop it once the
+        // switch to a lower frequency is complete.
+        if !scale.requires_over_drive() {
+            self.pwr.disable_over_drive();
+        }
+
+        // Configuration is final: publish the frozen frequency snapshot before
+        // any peripheral reads its clock.
+        freeze_from(self.rcc);
+
+        Ok(())
+    }
+}
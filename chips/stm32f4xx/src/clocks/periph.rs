@@ -2,18 +2,48 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 // Copyright Tock Contributors 2024.
 
+use core::cell::Cell;
+
 use crate::chip_specific::ChipSpecs;
 use crate::clocks::Clocks;
 use crate::gpio::{GpioPort, GPIO_NUM_PORTS};
 use crate::rcc::{APBPrescaler, Rcc, RtcClockSource};
 use kernel::platform::chip::ClockInterface;
 
+/// Frequency of the low-speed internal (LSI) RC oscillator, ~32 kHz.
+const LSI_FREQUENCY: u32 = 32_000;
+/// Frequency of the low-speed external (LSE) crystal, 32.768 kHz.
+const LSE_FREQUENCY: u32 = 32_768;
+
 /// Extension to ClockInterface
 pub trait PeripheralClockInterface: ClockInterface {
     fn get_frequency(&self) -> u32;
+    fn get_timer_frequency(&self) -> u32;
     fn configure(&self);
 }
 
+/// Clock seen by an APB timer, accounting for the TIMPRE doubling rule.
+///
+/// Reference Manual RM0090 section 6.2:
+/// When TIMPRE bit of the RCC_DCKCFGR register is reset, if APBx prescaler is 1, then
+/// TIMxCLK = PCLKx, otherwise TIMxCLK = 2x PCLKx.
+/// When TIMPRE bit in the RCC_DCKCFGR register is set, if APBx prescaler is 1,2 or 4,
+/// then TIMxCLK = HCLK, otherwise TIMxCLK = 4x PCLKx.
+#[inline(always)]
+pub(crate) fn tim_freq(rcc: &Rcc, hclk_freq: usize, prescaler: APBPrescaler) -> usize {
+    if !rcc.is_enabled_tim_pre() {
+        match prescaler {
+            APBPrescaler::DivideBy1 | APBPrescaler::DivideBy2 => hclk_freq,
+            _ => hclk_freq / usize::from(prescaler) * 2,
+        }
+    } else {
+        match prescaler {
+            APBPrescaler::DivideBy1 | APBPrescaler::DivideBy2 | APBPrescaler::DivideBy4 => hclk_freq,
+            _ => hclk_freq / usize::from(prescaler) * 4,
+        }
+    }
+}
+
 pub struct PeripheralClock<'a, C>
 where
     C: ChipSpecs,
@@ -21,6 +51,12 @@ where
     pub clock: PeripheralClockType,
     clocks: &'a Clocks<'a, C>,
     rcc: &'a Rcc,
+    /// Source selected for the RTC; only meaningful for [`PeripheralClockType::RTC`].
+    rtc_source: Cell<RtcClockSource>,
+    /// HSE frequency (Hz) and RTCPRE divider used when the RTC runs off the
+    /// HSE; set by the board via [`PeripheralClock::set_rtc_hse`]. Only
+    /// meaningful for [`RtcClockSource::HSE`].
+    rtc_hse: Cell<(u32, u32)>,
 }
 
 /// Bus + Clock name for the peripherals
@@ -82,7 +118,28 @@ where
     C: ChipSpecs,
 {
     pub const fn new(clock: PeripheralClockType, rcc: &'a Rcc, clocks: &'a Clocks<'a, C>) -> Self {
-        Self { clock, rcc, clocks }
+        Self {
+            clock,
+            rcc,
+            clocks,
+            rtc_source: Cell::new(RtcClockSource::LSI),
+            rtc_hse: Cell::new((0, 0)),
+        }
+    }
+
+    /// Select the clock source driving the RTC.
+    ///
+    /// Boards call this before enabling the RTC peripheral clock to pick LSE or
+    /// HSE instead of the default LSI.
+    pub fn set_rtc_source(&self, source: RtcClockSource) {
+        self.rtc_source.set(source);
+    }
+
+    /// Provide the HSE frequency (Hz) and RTCPRE divider used when the RTC is
+    /// clocked from the HSE. A divider of 0 or 1 disables the HSE RTC clock
+    /// (RM0090 §7.3.15). Boards call this alongside `set_rtc_source(HSE)`.
+    pub fn set_rtc_hse(&self, hse_frequency: u32, prescaler: u32) {
+        self.rtc_hse.set((hse_frequency, prescaler));
     }
 }
 
@@ -209,7 +266,7 @@ where
                     self.rcc.enable_syscfg_clock();
                 }
             },
-            PeripheralClockType::RTC => self.rcc.enable_rtc_clock(RtcClockSource::LSI),
+            PeripheralClockType::RTC => self.rcc.enable_rtc_clock(self.rtc_source.get()),
             PeripheralClockType::PWR => self.rcc.enable_pwr_clock(),
         }
     }
@@ -304,46 +361,41 @@ where
     C: ChipSpecs,
 {
     fn get_frequency(&self) -> u32 {
-        #[inline(always)]
-        fn tim_freq(rcc: &Rcc, hclk_freq: usize, prescaler: APBPrescaler) -> usize {
-            // Reference Manual RM0090 section 6.2
-            // When TIMPRE bit of the RCC_DCKCFGR register is reset, if APBx prescaler is 1, then
-            // TIMxCLK = PCLKx, otherwise TIMxCLK = 2x PCLKx.
-            // When TIMPRE bit in the RCC_DCKCFGR register is set, if APBx prescaler is 1,2 or 4,
-            // then TIMxCLK = HCLK, otherwise TIMxCLK = 4x PCLKx.
-            if !rcc.is_enabled_tim_pre() {
-                match prescaler {
-                    APBPrescaler::DivideBy1 | APBPrescaler::DivideBy2 => hclk_freq,
-                    _ => hclk_freq / usize::from(prescaler) * 2,
-                }
-            } else {
-                match prescaler {
-                    APBPrescaler::DivideBy1 | APBPrescaler::DivideBy2 | APBPrescaler::DivideBy4 => {
-                        hclk_freq
-                    }
-                    _ => hclk_freq / usize::from(prescaler) * 4,
-                }
-            }
-        }
-        let hclk_freq = self.rcc.get_sys_clock_frequency();
+        let freqs = crate::clocks::get_freqs();
         match self.clock {
             PeripheralClockType::AHB1(_)
             | PeripheralClockType::AHB2(_)
-            | PeripheralClockType::AHB3(_) => hclk_freq as u32,
-            PeripheralClockType::APB1(ref v) => {
-                let prescaler = self.rcc.get_apb1_prescaler();
-                match v {
-                    PCLK1::TIM2 => tim_freq(self.rcc, hclk_freq, prescaler) as u32,
-                    _ => (hclk_freq / usize::from(prescaler)) as u32,
-                }
-            }
-            PeripheralClockType::APB2(_) => {
-                let prescaler = self.rcc.get_apb2_prescaler();
-                (hclk_freq / usize::from(prescaler)) as u32
-            }
-            //TODO: implement clock frequency retrieval for RTC and PWR peripherals
-            PeripheralClockType::RTC => todo!(),
-            PeripheralClockType::PWR => todo!(),
+            | PeripheralClockType::AHB3(_) => freqs.ahb,
+            // Timers apply the TIMPRE doubling rule even when queried through
+            // the generic accessor, so existing callers keep the right rate.
+            PeripheralClockType::APB1(PCLK1::TIM2) => freqs.apb1_timer,
+            PeripheralClockType::APB1(_) => freqs.apb1,
+            PeripheralClockType::APB2(_) => freqs.apb2,
+            PeripheralClockType::RTC => match self.rtc_source.get() {
+                RtcClockSource::LSI => LSI_FREQUENCY,
+                RtcClockSource::LSE => LSE_FREQUENCY,
+                // The HSE is divided by the RTCPRE field before it reaches the
+                // RTC. A prescaler of 0 or 1 means "no clock" per RM0090 §7.3.15.
+                RtcClockSource::HSE => match self.rtc_hse.get() {
+                    (_, 0) | (_, 1) => 0,
+                    (hse_frequency, prescaler) => hse_frequency / prescaler,
+                },
+            },
+            // The PWR peripheral is gated on PCLK1 (APB1).
+            PeripheralClockType::PWR => freqs.apb1,
+        }
+    }
+
+    fn get_timer_frequency(&self) -> u32 {
+        let freqs = crate::clocks::get_freqs();
+        // The TIMPRE doubling rule applies symmetrically to APB1 and APB2
+        // timers, each using its own bus prescaler. Every APB1/APB2 peripheral
+        // gets its bus's timer clock here, timer or not; AHB, RTC and PWR
+        // clocks fall back to `get_frequency`.
+        match self.clock {
+            PeripheralClockType::APB1(_) => freqs.apb1_timer,
+            PeripheralClockType::APB2(_) => freqs.apb2_timer,
+            _ => self.get_frequency(),
         }
     }
 
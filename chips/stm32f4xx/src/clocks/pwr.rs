@@ -0,0 +1,175 @@
+// Licensed under the Apache License, Version 2.0 or the MIT License.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright Tock Contributors 2024.
+
+//! Power controller (PWR) voltage scaling.
+//!
+//! The PWR block selects the internal regulator voltage used to drive the core
+//! logic. A lower voltage scale saves power but caps the maximum system clock;
+//! the highest frequencies additionally require the over-drive mode. This
+//! module programs the `VOS` bits of `PWR_CR` and sequences over-drive as
+//! described in RM0090 §5.1.4 so the [`Clocks`](super::Clocks) configuration
+//! path can run the core at its full rated frequency.
+
+use kernel::utilities::registers::interfaces::{ReadWriteable, Readable};
+use kernel::utilities::registers::{register_bitfields, FieldValue, ReadWrite};
+use kernel::utilities::StaticRef;
+use kernel::ErrorCode;
+
+/// Base address of the PWR peripheral (RM0090 §2.3 memory map).
+const PWR_BASE: StaticRef<PwrRegisters> =
+    unsafe { StaticRef::new(0x4000_7000 as *const PwrRegisters) };
+
+#[repr(C)]
+pub struct PwrRegisters {
+    /// Power control register
+    cr: ReadWrite<u32, Control::Register>,
+    /// Power control/status register
+    csr: ReadWrite<u32, Status::Register>,
+}
+
+register_bitfields![u32,
+    Control [
+        /// Over-drive switching enable
+        ODSWEN OFFSET(17) NUMBITS(1) [],
+        /// Over-drive enable
+        ODEN OFFSET(16) NUMBITS(1) [],
+        /// Regulator voltage scaling output selection
+        VOS OFFSET(14) NUMBITS(2) [
+            Scale3 = 0b01,
+            Scale2 = 0b10,
+            Scale1 = 0b11
+        ]
+    ],
+    Status [
+        /// Over-drive mode switching ready
+        ODSWRDY OFFSET(17) NUMBITS(1) [],
+        /// Over-drive mode ready
+        ODRDY OFFSET(16) NUMBITS(1) [],
+        /// Regulator voltage scaling output selection ready
+        VOSRDY OFFSET(14) NUMBITS(1) []
+    ]
+];
+
+/// Internal regulator voltage scale.
+///
+/// Each scale legally supports system clock frequencies up to a fixed limit
+/// (RM0090 §5.1.4). The highest frequencies are only reachable in
+/// [`VoltageScale::Scale1`] with over-drive enabled.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum VoltageScale {
+    /// Up to 120 MHz.
+    Scale3,
+    /// Up to 144 MHz.
+    Scale2,
+    /// Up to 168 MHz without over-drive.
+    Scale1,
+    /// Up to 180 MHz with over-drive enabled.
+    Scale1OverDrive,
+}
+
+impl VoltageScale {
+    /// Maximum system clock frequency, in Hz, supported by this scale.
+    pub const fn max_sys_clock_frequency(&self) -> u32 {
+        match self {
+            VoltageScale::Scale3 => 120_000_000,
+            VoltageScale::Scale2 => 144_000_000,
+            VoltageScale::Scale1 => 168_000_000,
+            VoltageScale::Scale1OverDrive => 180_000_000,
+        }
+    }
+
+    /// Minimum voltage scale that legally supports `sys_clock_frequency`.
+    ///
+    /// Returns `None` when the requested frequency exceeds the highest
+    /// supported scale.
+    pub const fn minimum_for(sys_clock_frequency: u32) -> Option<VoltageScale> {
+        if sys_clock_frequency <= VoltageScale::Scale3.max_sys_clock_frequency() {
+            Some(VoltageScale::Scale3)
+        } else if sys_clock_frequency <= VoltageScale::Scale2.max_sys_clock_frequency() {
+            Some(VoltageScale::Scale2)
+        } else if sys_clock_frequency <= VoltageScale::Scale1.max_sys_clock_frequency() {
+            Some(VoltageScale::Scale1)
+        } else if sys_clock_frequency <= VoltageScale::Scale1OverDrive.max_sys_clock_frequency() {
+            Some(VoltageScale::Scale1OverDrive)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this scale needs over-drive enabled to be legal.
+    pub fn requires_over_drive(&self) -> bool {
+        matches!(self, VoltageScale::Scale1OverDrive)
+    }
+
+    fn vos(&self) -> FieldValue<u32, Control::Register> {
+        match self {
+            VoltageScale::Scale3 => Control::VOS::Scale3,
+            VoltageScale::Scale2 => Control::VOS::Scale2,
+            VoltageScale::Scale1 | VoltageScale::Scale1OverDrive => Control::VOS::Scale1,
+        }
+    }
+}
+
+pub struct Pwr {
+    registers: StaticRef<PwrRegisters>,
+}
+
+impl Pwr {
+    pub const fn new(registers: StaticRef<PwrRegisters>) -> Self {
+        Self { registers }
+    }
+
+    /// Construct a `Pwr` bound to the PWR peripheral's fixed base address.
+    ///
+    /// `Clocks` holds one of these and calls [`Pwr::configure_for_sys_clock`]
+    /// before switching the system clock (see that method's docs).
+    pub const fn new_default() -> Self {
+        Self::new(PWR_BASE)
+    }
+
+    /// Program the regulator voltage scale.
+    ///
+    /// This only selects the `VOS` level; over-drive is sequenced separately
+    /// via [`Pwr::enable_over_drive`] because it must be ordered relative to the
+    /// system-clock switch. The PWR clock must already be enabled.
+    pub fn set_voltage_scale(&self, scale: VoltageScale) {
+        self.registers.cr.modify(scale.vos());
+        while !self.registers.csr.is_set(Status::VOSRDY) {}
+    }
+
+    /// Enable over-drive mode, to be called *before* switching the system clock
+    /// to a frequency that requires [`VoltageScale::Scale1OverDrive`].
+    pub fn enable_over_drive(&self) {
+        self.registers.cr.modify(Control::ODEN::SET);
+        while !self.registers.csr.is_set(Status::ODRDY) {}
+        self.registers.cr.modify(Control::ODSWEN::SET);
+        while !self.registers.csr.is_set(Status::ODSWRDY) {}
+    }
+
+    /// Disable over-drive mode, to be called *after* switching the system clock
+    /// back down to a frequency that no longer requires it.
+    pub fn disable_over_drive(&self) {
+        self.registers.cr.modify(Control::ODSWEN::CLEAR);
+        self.registers.cr.modify(Control::ODEN::CLEAR);
+    }
+
+    /// Select the minimum voltage scale that legally supports
+    /// `sys_clock_frequency`, apply it, and enable over-drive if the scale
+    /// needs it. Call this *before* raising the system clock; the returned
+    /// scale lets the caller disable over-drive after lowering the clock.
+    ///
+    /// Returns [`ErrorCode::INVAL`] if no scale supports the requested
+    /// frequency, leaving the regulator untouched.
+    pub fn configure_for_sys_clock(
+        &self,
+        sys_clock_frequency: u32,
+    ) -> Result<VoltageScale, ErrorCode> {
+        let scale = VoltageScale::minimum_for(sys_clock_frequency).ok_or(ErrorCode::INVAL)?;
+        self.set_voltage_scale(scale);
+        if scale.requires_over_drive() {
+            self.enable_over_drive();
+        }
+        Ok(scale)
+    }
+}
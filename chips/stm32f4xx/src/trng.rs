@@ -14,6 +14,129 @@ use kernel::ErrorCode;
 
 use crate::clocks::PeripheralClockInterface;
 
+/// Error reported to the entropy client when a SP 800-90B continuous health
+/// test fails. Distinct from the hardware SEIS/CEIS handling so the client can
+/// tell a degraded noise source (data no longer trustworthy) apart from a
+/// transient seed/clock fault.
+#[cfg(feature = "trng_health_tests")]
+const HEALTH_TEST_FAILURE: ErrorCode = ErrorCode::NODEVICE;
+
+#[cfg(feature = "trng_health_tests")]
+mod health {
+    use core::cell::Cell;
+
+    /// Assumed lower bound on the min-entropy delivered per 32-bit RNG word.
+    ///
+    /// The cutoffs below are derived against this conservative estimate rather
+    /// than the full 32 bits: a healthy word carries far more, but testing
+    /// against a pessimistic `H` keeps the tests sensitive enough to fire while
+    /// the source is still only *partially* degraded instead of waiting for it
+    /// to collapse to a constant. `H = 8` bits (i.e. per-word collision
+    /// probability `2^-8`).
+    const MIN_ENTROPY_PER_WORD: u32 = 8;
+
+    /// Target false-positive rate, expressed as `-log2(alpha)` (alpha = 2^-20,
+    /// the low end of the SP 800-90B recommended 2^-20..2^-40 range).
+    const FALSE_POSITIVE_LOG2: u32 = 20;
+
+    /// Repetition Count Test cutoff `C` (NIST SP 800-90B §4.4.1):
+    /// `C = 1 + ceil(-log2(alpha) / H) = 1 + ceil(20 / 8) = 4`.
+    /// A run of four identical words has probability `<= alpha` under `H`, so it
+    /// flags a stuck source without tripping on the occasional legitimate pair.
+    const REPETITION_COUNT_CUTOFF: u32 = 1 + FALSE_POSITIVE_LOG2.div_ceil(MIN_ENTROPY_PER_WORD);
+
+    /// Adaptive Proportion Test window size `W` (NIST SP 800-90B §4.4.2).
+    const ADAPTIVE_PROPORTION_WINDOW: u32 = 1024;
+
+    /// Adaptive Proportion Test cutoff.
+    ///
+    /// Under `H = 8` the window's reference value recurs with probability
+    /// `2^-8`, so over `W = 1024` samples the match count is binomial with mean
+    /// `W * 2^-H = 4`. The smallest `C` with `P(X >= C) <= alpha = 2^-20` is 18
+    /// (Poisson(4) upper tail); the test fails once matches reach `C`. A source
+    /// that has lost entropy down to a few bits drives the mean well past this
+    /// bound, so the test actually fires.
+    const ADAPTIVE_PROPORTION_CUTOFF: u32 = 18;
+
+    /// Continuous health-test state carried between delivered words.
+    pub struct HealthTests {
+        // Repetition Count Test.
+        last_sample: Cell<u32>,
+        repetition_count: Cell<u32>,
+        // Adaptive Proportion Test.
+        window_reference: Cell<u32>,
+        window_position: Cell<u32>,
+        window_count: Cell<u32>,
+        started: Cell<bool>,
+    }
+
+    impl HealthTests {
+        pub const fn new() -> Self {
+            Self {
+                last_sample: Cell::new(0),
+                repetition_count: Cell::new(0),
+                window_reference: Cell::new(0),
+                window_position: Cell::new(0),
+                window_count: Cell::new(0),
+                started: Cell::new(false),
+            }
+        }
+
+        /// Run both continuous tests against `sample`, returning `Err(())` if
+        /// either test fails.
+        pub fn check(&self, sample: u32) -> Result<(), ()> {
+            // Repetition Count Test. The first sample after reset seeds the
+            // state (the SP 800-90B startup condition); thereafter a run of
+            // identical words is counted.
+            if self.started.get() && sample == self.last_sample.get() {
+                let count = self.repetition_count.get() + 1;
+                self.repetition_count.set(count);
+                if count >= REPETITION_COUNT_CUTOFF {
+                    return Err(());
+                }
+            } else {
+                self.last_sample.set(sample);
+                self.repetition_count.set(1);
+            }
+
+            // Adaptive Proportion Test over a window of exactly W samples: the
+            // first sample of each window is the reference and counts as one
+            // occurrence, then the following W-1 samples are compared against
+            // it. `window_position` counts samples placed in the current window
+            // (1..=W) and wraps back to 0 once the window is full.
+            if self.window_position.get() == 0 {
+                self.window_reference.set(sample);
+                self.window_count.set(1);
+                self.window_position.set(1);
+            } else {
+                if sample == self.window_reference.get() {
+                    let count = self.window_count.get() + 1;
+                    self.window_count.set(count);
+                    if count >= ADAPTIVE_PROPORTION_CUTOFF {
+                        return Err(());
+                    }
+                }
+                let position = self.window_position.get() + 1;
+                self.window_position.set(if position >= ADAPTIVE_PROPORTION_WINDOW {
+                    0
+                } else {
+                    position
+                });
+            }
+
+            self.started.set(true);
+            Ok(())
+        }
+
+        /// Discard all accumulated state so the next sample restarts startup
+        /// testing. Called after the RNG is reseeded.
+        pub fn reset(&self) {
+            self.started.set(false);
+            self.window_position.set(0);
+        }
+    }
+}
+
 #[repr(C)]
 pub struct RngRegisters {
     cr: ReadWrite<u32, Control::Register>,
@@ -55,6 +178,10 @@ pub struct Trng<'a> {
     registers: StaticRef<RngRegisters>,
     clock: &'a dyn PeripheralClockInterface,
     client: OptionalCell<&'a dyn hil::entropy::Client32>,
+    #[cfg(feature = "trng_health_tests")]
+    health: health::HealthTests,
+    #[cfg(feature = "trng_health_tests")]
+    health_failed: core::cell::Cell<bool>,
 }
 
 impl<'a> Trng<'a> {
@@ -66,9 +193,21 @@ impl<'a> Trng<'a> {
             registers: registers,
             clock,
             client: OptionalCell::empty(),
+            #[cfg(feature = "trng_health_tests")]
+            health: health::HealthTests::new(),
+            #[cfg(feature = "trng_health_tests")]
+            health_failed: core::cell::Cell::new(false),
         }
     }
 
+    /// Clear and restart the RNG to force the noise source to reseed.
+    #[cfg(feature = "trng_health_tests")]
+    fn reseed(&self) {
+        self.registers.cr.modify(Control::RNGEN::CLEAR);
+        self.registers.cr.modify(Control::RNGEN::SET);
+        self.health.reset();
+    }
+
     pub fn is_enabled_clock(&self) -> bool {
         self.clock.is_enabled()
     }
@@ -105,6 +244,15 @@ impl<'a> Trng<'a> {
                 self.registers.cr.modify(Control::RNGEN::CLEAR);
             }
         });
+
+        // A continuous health-test failure detected while iterating stops
+        // delivery and reseeds the RNG; report it to the client out of band.
+        #[cfg(feature = "trng_health_tests")]
+        if self.health_failed.take() {
+            self.client.map(|client| {
+                let _ = client.entropy_available(&mut TrngIter(self), Err(HEALTH_TEST_FAILURE));
+            });
+        }
     }
 }
 
@@ -116,7 +264,18 @@ impl Iterator for TrngIter<'_, '_> {
     fn next(&mut self) -> Option<u32> {
         if self.0.registers.sr.is_set(Status::DRDY) {
             // This also clears the DRDY bit in the Status register.
-            Some(self.0.registers.data.read(Data::RNDATA))
+            let word = self.0.registers.data.read(Data::RNDATA);
+
+            // Route the word through the continuous health tests before
+            // yielding it. On failure, stop delivering and reseed the source.
+            #[cfg(feature = "trng_health_tests")]
+            if self.0.health.check(word).is_err() {
+                self.0.health_failed.set(true);
+                self.0.reseed();
+                return None;
+            }
+
+            Some(word)
         } else {
             None
         }